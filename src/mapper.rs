@@ -1,21 +1,28 @@
-use std::fs;
-use std::io;
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Result;
 use futures::future;
 use hyper::{Body, Request, Response, StatusCode};
 use hyper::service::Service;
-use hyper::header::{self, HeaderValue};
+use hyper::header::{self, HeaderName, HeaderValue};
+use serde::Deserialize;
 
 pub mod dir_picker;
 pub mod file_picker;
+pub mod filesystem;
 pub mod mutation;
+pub mod watch;
 
 pub use crate::mapper::dir_picker::{DirPicker, StandardDirPicker};
 pub use crate::mapper::file_picker::{FilePicker, StandardFilePicker};
+pub use crate::mapper::filesystem::{Filesystem, MemFs, RealFs};
 pub use crate::mapper::mutation::ResponseMutation;
 
 use crate::{CounterfeitRunConfig, MultiFileIndexMap};
@@ -29,6 +36,7 @@ where
     file_picker: F,
     mutations: Vec<Box<dyn ResponseMutation>>,
     config: CounterfeitRunConfig,
+    fs: Arc<dyn Filesystem>,
 }
 
 impl<D, F> FileMapperService<D, F>
@@ -41,12 +49,14 @@ where
         file_picker: F,
         mutations: Vec<Box<dyn ResponseMutation>>,
         config: CounterfeitRunConfig,
+        fs: Arc<dyn Filesystem>,
     ) -> Self {
         Self {
             dir_picker,
             file_picker,
             mutations,
             config,
+            fs,
         }
     }
 
@@ -56,12 +66,17 @@ where
 }
 
 impl FileMapperService<StandardDirPicker, StandardFilePicker> {
-    pub fn standard(config: CounterfeitRunConfig, index_map: MultiFileIndexMap) -> Self {
+    pub fn standard(
+        config: CounterfeitRunConfig,
+        index_map: MultiFileIndexMap,
+        fs: Arc<dyn Filesystem>,
+    ) -> Self {
         Self {
-            dir_picker: StandardDirPicker::new(config.clone()),
-            file_picker: StandardFilePicker::new(config.create_missing, index_map),
+            dir_picker: StandardDirPicker::new(config.clone(), Arc::clone(&fs)),
+            file_picker: StandardFilePicker::new(config.create_missing, index_map, Arc::clone(&fs)),
             mutations: Vec::new(),
             config,
+            fs,
         }
     }
 }
@@ -73,7 +88,7 @@ where
 {
     type Response = Response<Body>;
     type Error = anyhow::Error;
-    type Future = future::Ready<Result<Self::Response, Self::Error>>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
 
     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         Poll::Ready(Ok(()))
@@ -87,21 +102,28 @@ where
         match self.dir_picker.pick_directory(&request) {
             Ok(directory) => {
                 let file = self.file_picker.pick_file(&directory, &request);
-                let mut output = MapperOutput::new(request, file);
-        
+                let mut output = MapperOutput::new(request, file, Arc::clone(&self.fs));
+
                 for mutation in self.mutations.iter() {
                     if let Err(e) = mutation.apply_mutation(&mut output) {
-                        return future::err(e.into());
+                        return Box::pin(future::err(e.into()));
                     }
                 }
-        
+
                 if !self.config.silent {
                     println!("Response: {} -> {}", output.response.status(), output);
                 }
-        
-                future::ok(output.into())
+
+                let delay = output.delay;
+                let response: Response<Body> = output.into();
+                Box::pin(async move {
+                    if let Some(delay) = delay {
+                        tokio::time::sleep(delay).await;
+                    }
+                    Ok(response)
+                })
             },
-            Err(e) => future::err(e.into()),
+            Err(e) => Box::pin(future::err(e.into())),
         }
     }
 }
@@ -109,13 +131,39 @@ where
 pub struct MakeFileMapperService {
     config: CounterfeitRunConfig,
     index_map: MultiFileIndexMap,
+    fs: Arc<dyn Filesystem>,
+    // Kept alive for the life of the service so the watch thread keeps running;
+    // dropping it stops the watch.
+    _watcher: Option<notify::RecommendedWatcher>,
 }
 
 impl MakeFileMapperService {
     pub fn new(config: CounterfeitRunConfig, index_map: MultiFileIndexMap) -> Self {
+        Self::with_filesystem(config, index_map, Arc::new(RealFs))
+    }
+
+    pub fn with_filesystem(
+        config: CounterfeitRunConfig,
+        index_map: MultiFileIndexMap,
+        fs: Arc<dyn Filesystem>,
+    ) -> Self {
+        let watcher = if config.watch {
+            match watch::watch_tree(&config.path, Arc::clone(&index_map), config.silent) {
+                Ok(watcher) => Some(watcher),
+                Err(e) => {
+                    eprintln!("Failed to watch {}: {}", config.path.display(), e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         Self {
             config,
             index_map,
+            fs,
+            _watcher: watcher,
         }
     }
 }
@@ -130,43 +178,153 @@ impl<T> Service<T> for MakeFileMapperService {
     }
 
     fn call(&mut self, _: T) -> Self::Future {
-        future::ok(FileMapperService::standard(self.config.clone(), Arc::clone(&self.index_map)))
+        future::ok(FileMapperService::standard(
+            self.config.clone(),
+            Arc::clone(&self.index_map),
+            Arc::clone(&self.fs),
+        ))
     }
 }
 
 pub type MapperResult = Result<PathBuf, io::Error>;
 
+/// Optional response-shaping metadata loaded from a fixture's sidecar JSON.
+///
+/// A fixture `get.json` is paired with `get.meta.json`; when present it lets a
+/// scenario override the status code, inject or override headers, pin a
+/// `Content-Type`, and delay the response without any code changes.
+#[derive(Debug, Deserialize)]
+pub struct FixtureMeta {
+    #[serde(default)]
+    pub status: Option<u16>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
+}
+
 #[derive(Debug)]
 pub struct MapperOutput {
     request: Request<Body>,
     response: Response<Body>,
     result: MapperResult,
+    delay: Option<Duration>,
 }
 
 impl MapperOutput {
-    pub fn new(request: Request<Body>, result: MapperResult) -> Self {
+    pub fn new(request: Request<Body>, result: MapperResult, fs: Arc<dyn Filesystem>) -> Self {
         let response = match &result {
-            Ok(path) => Self::response_from_file(path),
+            Ok(path) => {
+                Self::response_from_file(&fs, path, request.headers().get(header::RANGE))
+            }
             Err(e) => Self::response_from_error(e),
         };
 
-        Self {
+        let mut output = Self {
             request,
             response,
             result,
+            delay: None,
+        };
+
+        if let Ok(path) = &output.result {
+            if let Some(meta) = load_sidecar(&fs, path) {
+                output.apply_meta(meta);
+            }
         }
+
+        output
     }
 
-    fn response_from_file<P: AsRef<Path>>(file_path: P) -> Response<Body> {
-        match fs::read_to_string(&file_path) {
-            Ok(path) => {
-                let mut response = Response::new(Body::from(path));
-                *response.status_mut() = StatusCode::OK;
-                set_default_headers(&mut response);
-                response
-            },
-            Err(e) => Self::response_from_error(&e),
+    /// Overlay sidecar metadata onto the generated response: a `status`, when
+    /// given, replaces the response's status (absent, the generated one — e.g.
+    /// a `206` from a range request — is left untouched), `content_type` pins
+    /// the `Content-Type`, and `headers` are merged over the defaults. The
+    /// delay is stored for the service to honor.
+    fn apply_meta(&mut self, meta: FixtureMeta) {
+        if let Some(status) = meta.status {
+            if let Ok(status) = StatusCode::from_u16(status) {
+                *self.response.status_mut() = status;
+            }
         }
+
+        if let Some(content_type) = meta.content_type {
+            if let Ok(value) = HeaderValue::from_str(&content_type) {
+                self.response.headers_mut().insert(header::CONTENT_TYPE, value);
+            }
+        }
+
+        for (name, value) in meta.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                self.response.headers_mut().insert(name, value);
+            }
+        }
+
+        self.delay = meta.delay_ms.map(Duration::from_millis);
+    }
+
+    fn response_from_file<P: AsRef<Path>>(
+        fs: &Arc<dyn Filesystem>,
+        file_path: P,
+        range: Option<&HeaderValue>,
+    ) -> Response<Body> {
+        let path = file_path.as_ref();
+
+        let total = match fs.len(path) {
+            Ok(total) => total,
+            Err(e) => return Self::response_from_error(&e),
+        };
+
+        // The range-not-satisfiable body is a plain-text message; only the file
+        // paths below get a sniffed content type.
+        let (mut response, content_type) = match parse_range(range, total) {
+            RangeSpec::Unsatisfiable => {
+                let mut response = Response::new(Body::from(format!(
+                    "Requested range not satisfiable for {} byte file",
+                    total,
+                )));
+                *response.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+                response.headers_mut().insert(
+                    header::CONTENT_RANGE,
+                    HeaderValue::from_str(&format!("bytes */{}", total))
+                        .expect("content-range is valid ascii"),
+                );
+                (response, "text/plain")
+            }
+            RangeSpec::Satisfiable(start, end) => {
+                let size = end - start + 1;
+                let mut response = Response::new(stream_file(Arc::clone(fs), path.to_path_buf(), start, size));
+                *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+                response.headers_mut().insert(
+                    header::CONTENT_RANGE,
+                    HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, total))
+                        .expect("content-range is valid ascii"),
+                );
+                (response, guess_content_type(fs, path))
+            }
+            RangeSpec::None => {
+                let mut response = Response::new(stream_file(Arc::clone(fs), path.to_path_buf(), 0, total));
+                *response.status_mut() = StatusCode::OK;
+                (response, guess_content_type(fs, path))
+            }
+        };
+
+        set_default_headers(&mut response);
+        response.headers_mut().insert(
+            header::ACCEPT_RANGES,
+            HeaderValue::from_static("bytes"),
+        );
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(content_type)
+                .unwrap_or_else(|_| HeaderValue::from_static("application/octet-stream")),
+        );
+        response
     }
 
     fn response_from_error(error: &io::Error) -> Response<Body> {
@@ -215,6 +373,220 @@ impl From<MapperOutput> for Response<Body> {
     }
 }
 
+/// Load the `*.meta.json` sidecar for a fixture, if one exists.
+///
+/// `foo/get.json` resolves to `foo/get.meta.json`; a file without an extension
+/// simply gains a `.meta.json` suffix. A malformed sidecar is ignored so a bad
+/// edit degrades to the default response rather than failing the request.
+fn load_sidecar(fs: &Arc<dyn Filesystem>, file_path: &Path) -> Option<FixtureMeta> {
+    let stem = file_path.file_stem()?.to_str()?;
+    let sidecar = file_path.with_file_name(format!("{}.meta.json", stem));
+
+    if !fs.exists(&sidecar) {
+        return None;
+    }
+
+    let bytes = fs.read(&sidecar).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// A `Range` header resolved against the target file's length.
+enum RangeSpec {
+    /// No (or unparseable) range header; serve the whole file.
+    None,
+    /// An inclusive byte range that lies within the file.
+    Satisfiable(u64, u64),
+    /// A syntactically valid range that falls outside the file.
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=start-end` range against a file of `total` bytes.
+///
+/// Supports the open-ended `start-` and suffix `-len` forms, clamps `end` to
+/// the last byte, and treats anything it cannot make sense of as [`RangeSpec::None`]
+/// so the caller simply serves the whole file.
+fn parse_range(range: Option<&HeaderValue>, total: u64) -> RangeSpec {
+    let spec = match range.and_then(|value| value.to_str().ok()) {
+        Some(spec) => spec.trim(),
+        None => return RangeSpec::None,
+    };
+
+    let spec = match spec.strip_prefix("bytes=") {
+        Some(spec) => spec.trim(),
+        None => return RangeSpec::None,
+    };
+
+    // Only a single range is supported; bail on comma-separated sets.
+    if spec.contains(',') {
+        return RangeSpec::None;
+    }
+
+    let (start, end) = match spec.split_once('-') {
+        Some(parts) => parts,
+        None => return RangeSpec::None,
+    };
+
+    // For an empty file every parsed range ends up with `start >= total` below.
+    let last = total.saturating_sub(1);
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        // Suffix form `-len`: the last `len` bytes of the file.
+        ("", len) => match len.parse::<u64>() {
+            Ok(0) => return RangeSpec::Unsatisfiable,
+            Ok(len) => (total.saturating_sub(len), last),
+            Err(_) => return RangeSpec::None,
+        },
+        // Open-ended `start-`: from `start` to the end of the file.
+        (start, "") => match start.parse::<u64>() {
+            Ok(start) => (start, last),
+            Err(_) => return RangeSpec::None,
+        },
+        // Closed `start-end`, with `end` clamped to the final byte.
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) => (start, end.min(last)),
+            _ => return RangeSpec::None,
+        },
+    };
+
+    if start > end || start >= total {
+        RangeSpec::Unsatisfiable
+    } else {
+        RangeSpec::Satisfiable(start, end)
+    }
+}
+
+/// Stream `size` bytes of `path` starting at `offset` over a [`hyper::Body`]
+/// channel, reading one fixed-size chunk at a time off the blocking pool so a
+/// large fixture is never buffered into memory in full.
+fn stream_file(fs: Arc<dyn Filesystem>, path: PathBuf, offset: u64, size: u64) -> Body {
+    const CHUNK_SIZE: u64 = 64 * 1024;
+
+    let (mut sender, body) = Body::channel();
+
+    tokio::spawn(async move {
+        let mut reader = match fs.open(&path, offset) {
+            Ok(reader) => reader,
+            Err(e) => {
+                sender.abort();
+                if cfg!(debug_assertions) {
+                    eprintln!("Failed to open {} for streaming: {}", path.display(), e);
+                }
+                return;
+            }
+        };
+
+        let mut remaining = size;
+        while remaining > 0 {
+            let to_read = remaining.min(CHUNK_SIZE) as usize;
+
+            let read = tokio::task::spawn_blocking(move || {
+                let mut buffer = vec![0u8; to_read];
+                let result = reader.read(&mut buffer);
+                (reader, buffer, result)
+            })
+            .await;
+
+            let (returned_reader, mut buffer, result) = match read {
+                Ok(read) => read,
+                Err(_) => {
+                    sender.abort();
+                    return;
+                }
+            };
+            reader = returned_reader;
+
+            match result {
+                Ok(0) => break,
+                Ok(bytes_read) => {
+                    buffer.truncate(bytes_read);
+                    remaining -= bytes_read as u64;
+                    if sender.send_data(buffer.into()).await.is_err() {
+                        return;
+                    }
+                }
+                Err(_) => {
+                    sender.abort();
+                    return;
+                }
+            }
+        }
+    });
+
+    body
+}
+
+/// Read up to the leading 512 bytes of a file for content sniffing, ignoring
+/// errors. Bounded via a single `open`+`read` so a large fixture is never
+/// buffered into memory in full merely to guess its MIME type.
+fn read_prefix(fs: &Arc<dyn Filesystem>, path: &Path) -> Vec<u8> {
+    const MAX_SNIFF: usize = 512;
+
+    let mut reader = match fs.open(path, 0) {
+        Ok(reader) => reader,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut buffer = vec![0u8; MAX_SNIFF];
+    match reader.read(&mut buffer) {
+        Ok(read) => {
+            buffer.truncate(read);
+            buffer
+        }
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Best-effort MIME type for a fixture file.
+///
+/// The extension is trusted first via a small `mime_guess`-style table; only
+/// when it is unknown or absent do we read the leading bytes and sniff them for
+/// a handful of common magic numbers, falling back to `text/plain` for valid
+/// UTF-8 and `application/octet-stream` otherwise.
+fn guess_content_type(fs: &Arc<dyn Filesystem>, path: &Path) -> &'static str {
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        if let Some(content_type) = content_type_from_extension(&extension.to_lowercase()) {
+            return content_type;
+        }
+    }
+
+    sniff_content_type(&read_prefix(fs, path))
+}
+
+fn content_type_from_extension(extension: &str) -> Option<&'static str> {
+    let content_type = match extension {
+        "json" => "application/json",
+        "html" | "htm" => "text/html",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        _ => return None,
+    };
+
+    Some(content_type)
+}
+
+fn sniff_content_type(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        "image/png"
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if bytes.starts_with(b"%PDF-") {
+        "application/pdf"
+    } else if bytes.starts_with(&[0x00, 0x61, 0x73, 0x6D]) {
+        "application/wasm"
+    } else if std::str::from_utf8(bytes).is_ok() {
+        "text/plain"
+    } else {
+        "application/octet-stream"
+    }
+}
+
 fn set_default_headers(response: &mut Response<Body>) {
     response.headers_mut().insert(
         header::ACCESS_CONTROL_ALLOW_ORIGIN,
@@ -0,0 +1,85 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use hyper::{Body, Request};
+
+use crate::mapper::filesystem::Filesystem;
+use crate::CounterfeitRunConfig;
+
+/// Resolves an incoming request to the directory its fixtures live in.
+pub trait DirPicker {
+    fn pick_directory(&self, request: &Request<Body>) -> io::Result<PathBuf>;
+}
+
+/// Maps a request's URI path onto a directory beneath the served root.
+pub struct StandardDirPicker {
+    config: CounterfeitRunConfig,
+    fs: Arc<dyn Filesystem>,
+}
+
+impl StandardDirPicker {
+    pub fn new(config: CounterfeitRunConfig, fs: Arc<dyn Filesystem>) -> Self {
+        Self { config, fs }
+    }
+}
+
+impl DirPicker for StandardDirPicker {
+    fn pick_directory(&self, request: &Request<Body>) -> io::Result<PathBuf> {
+        let mut directory = self.config.path.clone();
+        for segment in request.uri().path().split('/').filter(|s| !s.is_empty()) {
+            directory.push(segment);
+        }
+
+        // When the directory is missing or empty we only proceed if the file
+        // picker is allowed to materialize missing fixtures; otherwise picking
+        // fails with `NotFound`.
+        let has_fixtures = self
+            .fs
+            .read_dir(&directory)
+            .map(|entries| !entries.is_empty())
+            .unwrap_or(false);
+        if self.config.create_missing || has_fixtures {
+            Ok(directory)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No mock directory for {}", request.uri().path()),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mapper::filesystem::MemFs;
+
+    fn config(path: &str, create_missing: bool) -> CounterfeitRunConfig {
+        let mut config = CounterfeitRunConfig::default();
+        config.path = PathBuf::from(path);
+        config.create_missing = create_missing;
+        config
+    }
+
+    fn request(path: &str) -> Request<Body> {
+        Request::builder().uri(path).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn maps_uri_segments_onto_served_root() {
+        let fs = MemFs::new();
+        fs.insert("mocks/users/1/get.json", "{}");
+        let picker = StandardDirPicker::new(config("mocks", false), Arc::new(fs));
+
+        let directory = picker.pick_directory(&request("/users/1")).unwrap();
+        assert_eq!(directory, PathBuf::from("mocks/users/1"));
+    }
+
+    #[test]
+    fn missing_directory_is_not_found_without_create_missing() {
+        let picker = StandardDirPicker::new(config("mocks", false), Arc::new(MemFs::new()));
+        let err = picker.pick_directory(&request("/absent")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
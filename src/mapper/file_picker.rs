@@ -0,0 +1,186 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use hyper::{Body, Request};
+
+use crate::mapper::filesystem::Filesystem;
+use crate::mapper::MapperResult;
+use crate::MultiFileIndexMap;
+
+/// Picks the fixture file within a directory that should answer a request.
+pub trait FilePicker {
+    fn pick_file(&self, directory: &Path, request: &Request<Body>) -> MapperResult;
+}
+
+/// Resolves a request to a method-matching fixture, round-robining over the
+/// matches when a directory holds several so repeated calls cycle through them.
+pub struct StandardFilePicker {
+    create_missing: bool,
+    multifile_indices: MultiFileIndexMap,
+    fs: Arc<dyn Filesystem>,
+}
+
+impl StandardFilePicker {
+    pub fn new(
+        create_missing: bool,
+        index_map: MultiFileIndexMap,
+        fs: Arc<dyn Filesystem>,
+    ) -> Self {
+        Self {
+            create_missing,
+            multifile_indices: index_map,
+            fs,
+        }
+    }
+}
+
+impl FilePicker for StandardFilePicker {
+    fn pick_file(&self, directory: &Path, request: &Request<Body>) -> MapperResult {
+        let method = request.method().as_str().to_lowercase();
+
+        let mut available_files = self
+            .fs
+            .read_dir(directory)?
+            .into_iter()
+            .filter(|path| self.fs.is_file(path))
+            .filter(|path| file_matches(path, &method))
+            .collect::<Vec<PathBuf>>();
+        // `read_dir` ordering is backend-defined; sort so the round-robin is
+        // stable across backends and across reloads.
+        available_files.sort();
+
+        if available_files.is_empty() {
+            if self.create_missing {
+                let path = directory.join(format!("{}.json", method));
+                self.fs.create_file(&path)?;
+                Ok(path)
+            } else {
+                Err(io::Error::new(io::ErrorKind::NotFound, "No files available"))
+            }
+        } else {
+            let mut indices = self.multifile_indices.lock().unwrap();
+            let index = indices.entry(PathBuf::from(directory)).or_insert(0);
+            if *index >= available_files.len() {
+                *index = 0;
+            }
+
+            let file = available_files
+                .into_iter()
+                .nth(*index)
+                .expect("index is bounded by the file count above");
+            *index += 1;
+            Ok(file)
+        }
+    }
+}
+
+/// Whether a fixture file answers `method`, matching `get.json` as well as the
+/// `get_*.json` multi-file form while skipping unrelated files and sidecars.
+fn file_matches(file_path: &Path, method: &str) -> bool {
+    // Sidecars shape a fixture's response; they are never served themselves, and
+    // `get_1.meta.json` would otherwise pass the `get_` stem test below.
+    if is_sidecar(file_path) {
+        return false;
+    }
+
+    match file_path.file_stem().and_then(|stem| stem.to_str()) {
+        Some(stem) => {
+            let stem = stem.to_lowercase();
+            stem == method || stem.starts_with(&format!("{}_", method))
+        }
+        None => false,
+    }
+}
+
+/// Whether `file_path` is a `*.meta.json` response-shaping sidecar.
+fn is_sidecar(file_path: &Path) -> bool {
+    file_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.ends_with(".meta.json"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use crate::mapper::filesystem::MemFs;
+
+    fn index_map() -> MultiFileIndexMap {
+        Arc::new(Mutex::new(HashMap::new()))
+    }
+
+    fn request(method: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri("/")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn round_robins_over_multiple_matching_files() {
+        let fs = MemFs::new();
+        fs.insert("api/get_1.json", "1");
+        fs.insert("api/get_2.json", "2");
+        fs.insert("api/post.json", "p");
+        let picker = StandardFilePicker::new(false, index_map(), Arc::new(fs));
+        let dir = Path::new("api");
+
+        assert_eq!(picker.pick_file(dir, &request("GET")).unwrap(), PathBuf::from("api/get_1.json"));
+        assert_eq!(picker.pick_file(dir, &request("GET")).unwrap(), PathBuf::from("api/get_2.json"));
+        // Wraps back around to the first match.
+        assert_eq!(picker.pick_file(dir, &request("GET")).unwrap(), PathBuf::from("api/get_1.json"));
+    }
+
+    #[test]
+    fn sidecars_are_excluded_from_the_rotation() {
+        let fs = MemFs::new();
+        fs.insert("api/get_1.json", "1");
+        fs.insert("api/get_1.meta.json", "{}");
+        fs.insert("api/get_2.json", "2");
+        fs.insert("api/get_2.meta.json", "{}");
+        let picker = StandardFilePicker::new(false, index_map(), Arc::new(fs));
+        let dir = Path::new("api");
+
+        // Only the two real fixtures rotate; the sidecars never appear.
+        assert_eq!(picker.pick_file(dir, &request("GET")).unwrap(), PathBuf::from("api/get_1.json"));
+        assert_eq!(picker.pick_file(dir, &request("GET")).unwrap(), PathBuf::from("api/get_2.json"));
+        assert_eq!(picker.pick_file(dir, &request("GET")).unwrap(), PathBuf::from("api/get_1.json"));
+    }
+
+    #[test]
+    fn resetting_the_index_restarts_the_rotation() {
+        let fs = MemFs::new();
+        fs.insert("api/get_1.json", "1");
+        fs.insert("api/get_2.json", "2");
+        let indices = index_map();
+        let picker = StandardFilePicker::new(false, Arc::clone(&indices), Arc::new(fs));
+        let dir = Path::new("api");
+
+        picker.pick_file(dir, &request("GET")).unwrap();
+        indices.lock().unwrap().remove(&PathBuf::from("api"));
+        // After a reset the next response starts from the first file again.
+        assert_eq!(picker.pick_file(dir, &request("GET")).unwrap(), PathBuf::from("api/get_1.json"));
+    }
+
+    #[test]
+    fn missing_files_error_without_create_missing() {
+        let picker = StandardFilePicker::new(false, index_map(), Arc::new(MemFs::new()));
+        let err = picker.pick_file(Path::new("api"), &request("GET")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn create_missing_materializes_a_method_fixture() {
+        let fs = Arc::new(MemFs::new());
+        let picker = StandardFilePicker::new(true, index_map(), Arc::clone(&fs));
+        let path = picker.pick_file(Path::new("api"), &request("GET")).unwrap();
+        assert_eq!(path, PathBuf::from("api/get.json"));
+        assert!(fs.exists(&path));
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Storage backend the mapper reads fixtures from.
+///
+/// Every filesystem touch in the mapping pipeline goes through this trait so
+/// the pickers and [`MapperOutput`](crate::mapper::MapperOutput) can be driven
+/// against an in-memory tree in tests, and so fixtures can one day come from a
+/// non-disk source (embedded bundles, remote stores) behind the same service.
+pub trait Filesystem: Send + Sync {
+    /// List the entries directly under `path`. [`RealFs`] includes
+    /// subdirectories; [`MemFs`] only ever holds files.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Read the full contents of `path`.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Create an empty file at `path`, including any missing parents.
+    fn create_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Report whether `path` exists.
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Report whether `path` is a regular file (as opposed to a directory).
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Length of `path` in bytes.
+    fn len(&self, path: &Path) -> io::Result<u64>;
+
+    /// Open `path` for reading, positioned `offset` bytes from the start.
+    fn open(&self, path: &Path, offset: u64) -> io::Result<Box<dyn Read + Send>>;
+}
+
+/// The real, disk-backed [`Filesystem`]. This is what counterfeit uses in
+/// production; the abstraction exists so tests don't have to.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl Filesystem for RealFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        Ok(std::fs::read_dir(path)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::File::create(path).map(|_| ())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn len(&self, path: &Path) -> io::Result<u64> {
+        std::fs::metadata(path).map(|metadata| metadata.len())
+    }
+
+    fn open(&self, path: &Path, offset: u64) -> io::Result<Box<dyn Read + Send>> {
+        use std::io::{Seek, SeekFrom};
+
+        let mut file = std::fs::File::open(path)?;
+        if offset > 0 {
+            file.seek(SeekFrom::Start(offset))?;
+        }
+        Ok(Box::new(file))
+    }
+}
+
+/// An in-memory [`Filesystem`] backed by a `BTreeMap`, for deterministic tests
+/// of the picker and multi-file index logic without touching disk.
+#[derive(Debug, Default)]
+pub struct MemFs {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+impl MemFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seed a file into the in-memory tree.
+    pub fn insert<P: Into<PathBuf>, B: Into<Vec<u8>>>(&self, path: P, contents: B) {
+        self.files.lock().unwrap().insert(path.into(), contents.into());
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} does not exist", path.display()),
+        )
+    }
+}
+
+impl Filesystem for MemFs {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        let files = self.files.lock().unwrap();
+        Ok(files
+            .keys()
+            .filter(|candidate| candidate.parent() == Some(path))
+            .cloned()
+            .collect())
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Self::not_found(path))
+    }
+
+    fn create_file(&self, path: &Path) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .entry(path.to_path_buf())
+            .or_default();
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        // The in-memory tree only ever holds files.
+        self.files.lock().unwrap().contains_key(path)
+    }
+
+    fn len(&self, path: &Path) -> io::Result<u64> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|contents| contents.len() as u64)
+            .ok_or_else(|| Self::not_found(path))
+    }
+
+    fn open(&self, path: &Path, offset: u64) -> io::Result<Box<dyn Read + Send>> {
+        let mut contents = self.read(path)?;
+        let offset = (offset as usize).min(contents.len());
+        contents.drain(..offset);
+        Ok(Box::new(Cursor::new(contents)))
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::MultiFileIndexMap;
+
+/// Watch `root` recursively and keep the round-robin index map coherent as
+/// fixtures change on disk.
+///
+/// When a file is created, removed, or renamed under a directory, that
+/// directory's entry in the shared [`MultiFileIndexMap`] is reset so the next
+/// sequential response starts from the first matching file again rather than
+/// drifting against a changed file set. The returned watcher must be kept alive
+/// for the duration of the mocking session; dropping it stops the watch.
+pub fn watch_tree<P: AsRef<Path>>(
+    root: P,
+    index_map: MultiFileIndexMap,
+    silent: bool,
+) -> Result<RecommendedWatcher> {
+    let root = root.as_ref().to_path_buf();
+    let watch_root = root.clone();
+    // The file picker keys the index map by `root`-relative directories, while
+    // notify reports OS-canonical paths; resolve the root once so events can be
+    // mapped back onto the same key form.
+    let canonical_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let event = match event {
+            Ok(event) => event,
+            Err(_) => return,
+        };
+
+        if !is_structural(&event.kind) {
+            return;
+        }
+
+        let mut indices = index_map.lock().unwrap();
+        for path in &event.paths {
+            if let Some(directory) = index_key(&root, &canonical_root, path) {
+                if indices.remove(&directory).is_some() && !silent {
+                    println!("Reloaded {} -> index reset", directory.display());
+                }
+            }
+        }
+    })?;
+
+    watcher.watch(&watch_root, RecursiveMode::Recursive)?;
+
+    Ok(watcher)
+}
+
+/// Map a notify event path to the [`MultiFileIndexMap`] key the file picker uses.
+///
+/// Events carry the changed file; its parent directory is the unit the index
+/// map is keyed on. The parent is canonicalized and re-expressed relative to
+/// `canonical_root`, then re-joined onto the served `root` so the result matches
+/// the `root`-relative form the picker inserted. Paths outside the watched root
+/// yield `None`.
+fn index_key(root: &Path, canonical_root: &Path, event_path: &Path) -> Option<PathBuf> {
+    let directory = event_path.parent()?;
+    let canonical_dir = directory
+        .canonicalize()
+        .unwrap_or_else(|_| directory.to_path_buf());
+    let relative = canonical_dir.strip_prefix(canonical_root).ok()?;
+    Some(root.join(relative))
+}
+
+/// Whether an event changes the set of files in a directory (as opposed to only
+/// their contents), which is what invalidates the round-robin counters.
+fn is_structural(kind: &EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn event_under_canonical_root_resets_matching_key() {
+        // Served root as the picker sees it (relative), plus its canonical form
+        // as notify would report it.
+        let canonical_root = std::env::temp_dir().join("counterfeit_watch_test");
+        std::fs::create_dir_all(canonical_root.join("users")).unwrap();
+        let canonical_root = canonical_root.canonicalize().unwrap();
+        let root = PathBuf::from("mocks");
+
+        // The file picker would have keyed this directory relative to `root`.
+        let key = root.join("users");
+        let index_map: MultiFileIndexMap =
+            Arc::new(Mutex::new(HashMap::from([(key.clone(), 3usize)])));
+
+        // A create event arrives with the OS-canonical path of a new fixture.
+        let event_path = canonical_root.join("users").join("get_2.json");
+        let directory = index_key(&root, &canonical_root, &event_path).unwrap();
+        assert_eq!(directory, key);
+
+        let removed = index_map.lock().unwrap().remove(&directory);
+        assert_eq!(removed, Some(3));
+        assert!(index_map.lock().unwrap().is_empty());
+
+        std::fs::remove_dir_all(&canonical_root).ok();
+    }
+}